@@ -0,0 +1,427 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Largest integer magnitude an f64 holds exactly.
+pub const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+
+// Arbitrary precision integer, stored as a sign and a little-endian list of
+// 32-bit limbs without trailing zero limbs. Zero is never negative.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    // Parses a string of ASCII decimal digits.
+    pub fn parse(digits: &str) -> Self {
+        let mut magnitude: Vec<u32> = Vec::new();
+        for digit in digits.bytes() {
+            let mut carry = (digit - b'0') as u64;
+            for limb in magnitude.iter_mut() {
+                let product = *limb as u64 * 10 + carry;
+                *limb = product as u32;
+                carry = product >> 32;
+            }
+            if carry > 0 {
+                magnitude.push(carry as u32);
+            }
+        }
+        Self::new(false, magnitude)
+    }
+
+    // Converts an integral, finite f64 exactly.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        if value == 0.0 {
+            return Some(Self::new(false, Vec::new()));
+        }
+
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let magnitude = if exponent >= 0 {
+            shift_left(
+                &[mantissa as u32, (mantissa >> 32) as u32],
+                exponent as usize,
+            )
+        } else {
+            let mantissa = mantissa >> -exponent;
+            vec![mantissa as u32, (mantissa >> 32) as u32]
+        };
+        Some(Self::new(value < 0.0, magnitude))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4_294_967_296.0 + limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    // Whether an f64 represents this value exactly.
+    pub fn fits_f64(&self) -> bool {
+        match self.magnitude.len() {
+            0 | 1 => true,
+            2 => ((self.magnitude[1] as u64) << 32 | self.magnitude[0] as u64) <= MAX_EXACT as u64,
+            _ => false,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn negate(&self) -> Self {
+        Self::new(!self.negative, self.magnitude.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> Self {
+        if self.negative == other.negative {
+            return Self::new(self.negative, add(&self.magnitude, &other.magnitude));
+        }
+        match compare(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                Self::new(other.negative, subtract(&other.magnitude, &self.magnitude))
+            }
+            _ => Self::new(self.negative, subtract(&self.magnitude, &other.magnitude)),
+        }
+    }
+
+    pub fn subtract(&self, other: &BigInt) -> Self {
+        self.add(&other.negate())
+    }
+
+    pub fn multiply(&self, other: &BigInt) -> Self {
+        let mut magnitude = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let product = a as u64 * b as u64 + magnitude[i + j] as u64 + carry;
+                magnitude[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            magnitude[i + other.magnitude.len()] = carry as u32;
+        }
+        Self::new(self.negative != other.negative, magnitude)
+    }
+
+    // Truncating division, with the remainder taking the sign of the dividend.
+    // Returns None when dividing by zero.
+    pub fn divide(&self, other: &BigInt) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let mut quotient = vec![0u32; self.magnitude.len()];
+        let mut remainder: Vec<u32> = Vec::new();
+        for bit in (0..self.magnitude.len() * 32).rev() {
+            remainder = shift_left(&remainder, 1);
+            if self.magnitude[bit / 32] >> (bit % 32) & 1 == 1 {
+                if remainder.is_empty() {
+                    remainder.push(1);
+                } else {
+                    remainder[0] |= 1;
+                }
+            }
+            if compare(&remainder, &other.magnitude) != Ordering::Less {
+                remainder = subtract(&remainder, &other.magnitude);
+                quotient[bit / 32] |= 1 << (bit % 32);
+            }
+        }
+        Some((
+            Self::new(self.negative != other.negative, quotient),
+            Self::new(self.negative, remainder),
+        ))
+    }
+
+    // Approximates self / other as an f64, for a remainder smaller than its
+    // divisor. Both sides are shifted right by the same number of bits until
+    // the divisor fits in an f64, which keeps operands past 1e308 finite.
+    pub fn ratio(&self, other: &BigInt) -> f64 {
+        let shift = bit_length(&other.magnitude).saturating_sub(1000);
+        let a = Self::new(self.negative, shift_right(&self.magnitude, shift));
+        let b = Self::new(other.negative, shift_right(&other.magnitude, shift));
+        a.to_f64() / b.to_f64()
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare(&self.magnitude, &other.magnitude),
+            (true, true) => compare(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, least significant first.
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let mut remainder: u64 = 0;
+            for limb in magnitude.iter_mut().rev() {
+                let current = remainder << 32 | *limb as u64;
+                *limb = (current / 1_000_000_000) as u32;
+                remainder = current % 1_000_000_000;
+            }
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+            chunks.push(remainder as u32);
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum: Vec<u32> = Vec::with_capacity(long.len() + 1);
+    let mut carry: u64 = 0;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// Assumes that `a` is at least as large as `b`.
+fn subtract(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference: Vec<u32> = Vec::with_capacity(a.len());
+    let mut borrow: i64 = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut total = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    while difference.last() == Some(&0) {
+        difference.pop();
+    }
+    difference
+}
+
+fn shift_left(a: &[u32], bits: usize) -> Vec<u32> {
+    let mut shifted = vec![0u32; bits / 32];
+    let offset = bits % 32;
+    let mut carry: u32 = 0;
+    for &limb in a {
+        if offset == 0 {
+            shifted.push(limb);
+        } else {
+            shifted.push(limb << offset | carry);
+            carry = limb >> (32 - offset);
+        }
+    }
+    if carry > 0 {
+        shifted.push(carry);
+    }
+    while shifted.last() == Some(&0) {
+        shifted.pop();
+    }
+    shifted
+}
+
+fn shift_right(a: &[u32], bits: usize) -> Vec<u32> {
+    let offset = bits % 32;
+    let a = a.get(bits / 32..).unwrap_or(&[]);
+    let mut shifted: Vec<u32> = Vec::with_capacity(a.len());
+    for (i, &limb) in a.iter().enumerate() {
+        if offset == 0 {
+            shifted.push(limb);
+        } else {
+            let next = *a.get(i + 1).unwrap_or(&0);
+            shifted.push(limb >> offset | next << (32 - offset));
+        }
+    }
+    while shifted.last() == Some(&0) {
+        shifted.pop();
+    }
+    shifted
+}
+
+fn bit_length(a: &[u32]) -> usize {
+    match a.last() {
+        Some(&top) => a.len() * 32 - top.leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigInt {
+        match digits.strip_prefix('-') {
+            Some(digits) => BigInt::parse(digits).negate(),
+            None => BigInt::parse(digits),
+        }
+    }
+
+    fn show(value: &BigInt) -> String {
+        format!("{:?}", value)
+    }
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        for digits in &[
+            "0",
+            "1",
+            "999999999",
+            "1000000000",
+            "1000000001",
+            "4294967295",
+            "4294967296",
+            "18446744073709551616",
+            "100000000000000000000000000000000000001",
+            "-123456789012345678901234567890",
+        ] {
+            assert_eq!(show(&big(digits)), *digits);
+        }
+        assert_eq!(show(&big("000042")), "42");
+        assert_eq!(show(&big("-0")), "0");
+    }
+
+    #[test]
+    fn exact_f64_boundary() {
+        let max_exact = big("9007199254740992");
+        assert!(max_exact.fits_f64());
+        assert!(max_exact.negate().fits_f64());
+        assert_eq!(max_exact.to_f64(), MAX_EXACT);
+
+        let past_exact = big("9007199254740993");
+        assert!(!past_exact.fits_f64());
+        assert!(!past_exact.negate().fits_f64());
+
+        assert_eq!(BigInt::from_f64(MAX_EXACT), Some(max_exact));
+        assert_eq!(
+            BigInt::from_f64(18446744073709551616.0),
+            Some(big("18446744073709551616"))
+        );
+        assert_eq!(BigInt::from_f64(-3.0), Some(big("-3")));
+        assert_eq!(BigInt::from_f64(0.5), None);
+        assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("987654321098765432109876543210");
+        assert_eq!(show(&a.add(&b)), "1111111110111111111011111111100");
+        assert_eq!(show(&a.subtract(&b)), "-864197532086419753208641975320");
+        assert_eq!(
+            show(&a.multiply(&b)),
+            "121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(show(&a.subtract(&a)), "0");
+        assert!(big("-5") < big("3"));
+        assert!(big("-5") < big("-3"));
+    }
+
+    #[test]
+    fn division_truncates_towards_zero() {
+        let cases = [
+            ("100000000000000000007", "10", "10000000000000000000", "7"),
+            (
+                "-100000000000000000007",
+                "10",
+                "-10000000000000000000",
+                "-7",
+            ),
+            ("100000000000000000007", "-10", "-10000000000000000000", "7"),
+            (
+                "-100000000000000000007",
+                "-10",
+                "10000000000000000000",
+                "-7",
+            ),
+            ("5", "100000000000000000000", "0", "5"),
+        ];
+        for (a, b, quotient, remainder) in &cases {
+            let (q, r) = big(a).divide(&big(b)).unwrap();
+            assert_eq!(
+                (show(&q), show(&r)),
+                (quotient.to_string(), remainder.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn ratio_past_f64_range() {
+        let ten_to_400 = big(&format!("1{}", "0".repeat(400)));
+        let (quotient, remainder) = ten_to_400.divide(&big("3")).unwrap();
+        assert!(quotient.to_f64().is_infinite());
+        assert_eq!(show(&remainder), "1");
+
+        let larger = ten_to_400.add(&big("1"));
+        let (quotient, remainder) = ten_to_400.divide(&larger).unwrap();
+        assert!(quotient.is_zero());
+        assert_eq!(remainder.ratio(&larger), 1.0);
+
+        let half = big(&format!("5{}", "0".repeat(399)));
+        let (quotient, remainder) = ten_to_400.add(&half).divide(&ten_to_400).unwrap();
+        assert_eq!(quotient.to_f64() + remainder.ratio(&ten_to_400), 1.5);
+        assert_eq!(remainder.negate().ratio(&ten_to_400), -0.5);
+        assert_eq!(big("1").ratio(&big("4")), 0.25);
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert!(big("12345678901234567890").divide(&big("0")).is_none());
+        assert!(big("0").divide(&big("0")).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::chunk::*;
+use crate::value::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Type {
+    Number,
+    Boolean,
+    Nil,
+    Tuple,
+    Range,
+    Any,
+}
+
+impl Type {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Number(_) | Value::BigInt(_) => Type::Number,
+            Value::Boolean(_) => Type::Boolean,
+            Value::Nil => Type::Nil,
+            Value::Tuple(_) => Type::Tuple,
+            Value::Range(..) => Type::Range,
+        }
+    }
+
+    fn join(self, other: Type) -> Self {
+        if self == other {
+            self
+        } else {
+            Type::Any
+        }
+    }
+
+    fn is_number(self) -> bool {
+        self == Type::Number || self == Type::Any
+    }
+}
+
+// Infers the type of every stack slot by walking the chunk once, without
+// running it. Jumps only ever go forward, so every path into an operator has
+// been seen by the time it is reached.
+struct Checker<'ch> {
+    chunk: &'ch Chunk,
+    stacks: Vec<Option<Vec<Type>>>,
+    had_error: bool,
+}
+
+impl<'ch> Checker<'ch> {
+    fn new(chunk: &'ch Chunk) -> Self {
+        let mut stacks = vec![None; chunk.code.len()];
+        if !stacks.is_empty() {
+            stacks[0] = Some(Vec::new());
+        }
+        Self {
+            chunk,
+            stacks,
+            had_error: false,
+        }
+    }
+
+    fn run(&mut self) {
+        for offset in 0..self.chunk.code.len() {
+            let mut stack = match self.stacks[offset].take() {
+                Some(stack) => stack,
+                None => continue,
+            };
+
+            match self.chunk.code[offset] {
+                Op::Constant(index) => stack.push(Type::of(&self.chunk.values[index])),
+                Op::False | Op::True => stack.push(Type::Boolean),
+                Op::Nil => stack.push(Type::Nil),
+                Op::Negate => {
+                    if !stack.pop().unwrap().is_number() {
+                        self.error(offset, "Operand must be a number.");
+                    }
+                    stack.push(Type::Number);
+                }
+                Op::Add | Op::Subtract | Op::Multiply | Op::Divide => {
+                    self.numeric_operands(offset, &mut stack);
+                    stack.push(Type::Number);
+                }
+                Op::Greater | Op::GreaterEqual | Op::Less | Op::LessEqual => {
+                    self.numeric_operands(offset, &mut stack);
+                    stack.push(Type::Boolean);
+                }
+                Op::Not => {
+                    stack.pop();
+                    stack.push(Type::Boolean);
+                }
+                Op::Equal | Op::NotEqual => {
+                    stack.pop();
+                    stack.pop();
+                    stack.push(Type::Boolean);
+                }
+                Op::Tuple(count) => {
+                    stack.truncate(stack.len() - count);
+                    stack.push(Type::Tuple);
+                }
+                Op::TupleConcat(count) => {
+                    let parts = stack.split_off(stack.len() - count);
+                    if !parts
+                        .iter()
+                        .all(|&part| part == Type::Tuple || part == Type::Any)
+                    {
+                        self.error(offset, "Can only spread tuples.");
+                    }
+                    stack.push(Type::Tuple);
+                }
+                Op::Range | Op::RangeInclusive => {
+                    let b = stack.pop().unwrap();
+                    let a = stack.pop().unwrap();
+                    if !a.is_number() || !b.is_number() {
+                        self.error(offset, "Range bounds must be numbers.");
+                    }
+                    stack.push(Type::Range);
+                }
+                Op::Slice => {
+                    let bounds = stack.split_off(stack.len() - 3);
+                    let sequence = stack.pop().unwrap();
+                    if sequence != Type::Tuple && sequence != Type::Any {
+                        self.error(offset, "Only tuples can be sliced.");
+                    } else if !bounds
+                        .iter()
+                        .all(|&bound| bound.is_number() || bound == Type::Nil)
+                    {
+                        self.error(offset, "Slice indices must be integers.");
+                    }
+                    stack.push(Type::Tuple);
+                }
+                Op::Pop => {
+                    stack.pop();
+                }
+                Op::Dup => {
+                    let top = *stack.last().unwrap();
+                    stack.push(top);
+                }
+                Op::Jump(jump) => {
+                    self.flow(offset + 1 + jump, stack);
+                    continue;
+                }
+                Op::JumpIfFalse(jump) | Op::JumpIfNotNil(jump) => {
+                    self.flow(offset + 1 + jump, stack.clone())
+                }
+                Op::NoMatch | Op::Throw | Op::Return => continue,
+            }
+            self.flow(offset + 1, stack);
+        }
+    }
+
+    fn numeric_operands(&mut self, offset: usize, stack: &mut Vec<Type>) {
+        let b = stack.pop().unwrap();
+        let a = stack.pop().unwrap();
+        if !a.is_number() || !b.is_number() {
+            self.error(offset, "Operands must be numbers.");
+        }
+    }
+
+    fn flow(&mut self, target: usize, stack: Vec<Type>) {
+        self.stacks[target] = match self.stacks[target].take() {
+            Some(other) => Some(stack.iter().zip(other).map(|(a, b)| a.join(b)).collect()),
+            None => Some(stack),
+        };
+    }
+
+    fn error(&mut self, offset: usize, message: &str) {
+        self.had_error = true;
+        eprintln!("[line {}] Error: {}", self.chunk.get_line(offset), message);
+    }
+}
+
+pub fn check(chunk: &Chunk) -> bool {
+    let mut checker = Checker::new(chunk);
+    checker.run();
+    !checker.had_error
+}
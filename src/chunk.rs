@@ -19,13 +19,25 @@ pub enum Op {
     GreaterEqual,
     Less,
     LessEqual,
+    Pop,
+    Dup,
+    Jump(usize),
+    JumpIfFalse(usize),
+    JumpIfNotNil(usize),
+    NoMatch,
+    Throw,
+    Tuple(usize),
+    TupleConcat(usize),
+    Range,
+    RangeInclusive,
+    Slice,
 }
 
 #[derive(Default)]
 pub struct Chunk {
     pub code: Vec<Op>,
     pub values: Vec<Value>,
-    pub lines: Vec<(u16, u8)>, // Caps source files to 65_535 lines, with max 255 operators per line.
+    pub lines: Vec<(u16, u8)>, // Caps source files to 65_535 lines. Lines with more than 255 operators take several runs.
 }
 impl Chunk {
     pub fn new() -> Self {
@@ -41,23 +53,22 @@ impl Chunk {
         self.code.push(operator);
     }
 
-    pub fn add_constant(&mut self, value: Value, line: u16) -> usize {
-        self.update_lines(line);
+    pub fn add_constant(&mut self, value: Value) -> usize {
         self.values.push(value);
         self.values.len() - 1
     }
 
     // Assumes that lines are added in order
     fn update_lines(&mut self, line: u16) {
-        if self.lines.len() == 0 {
+        if self.lines.is_empty() {
             self.lines.push((line, 1));
         } else {
             let highest_index = self.lines.len() - 1;
             let (highest_line, ops) = self.lines[highest_index];
-            if line == highest_line {
+            if line == highest_line && ops < u8::MAX {
                 self.lines[highest_index] = (highest_line, ops + 1);
             } else {
-                self.lines.push((highest_line + 1, 1));
+                self.lines.push((line, 1));
             }
         }
     }
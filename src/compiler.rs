@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::bigint::BigInt;
 use crate::chunk::*;
 use crate::scanner::*;
 use crate::value::*;
@@ -8,10 +9,12 @@ use crate::value::*;
 enum Precedence {
     None,
     Assignment,
+    Coalesce,
     Or,
     And,
     Equality,
     Comparison,
+    Range,
     Term,
     Factor,
     Unary,
@@ -23,11 +26,13 @@ impl Precedence {
     pub fn next(&self) -> Self {
         match self {
             Precedence::None => Precedence::Assignment,
-            Precedence::Assignment => Precedence::Or,
+            Precedence::Assignment => Precedence::Coalesce,
+            Precedence::Coalesce => Precedence::Or,
             Precedence::Or => Precedence::And,
             Precedence::And => Precedence::Equality,
             Precedence::Equality => Precedence::Comparison,
-            Precedence::Comparison => Precedence::Term,
+            Precedence::Comparison => Precedence::Range,
+            Precedence::Range => Precedence::Term,
             Precedence::Term => Precedence::Factor,
             Precedence::Factor => Precedence::Unary,
             Precedence::Unary => Precedence::Call,
@@ -85,8 +90,12 @@ impl<'sc> Parser<'sc> {
         rule(RightParen, None, None, P::None);
         rule(LeftBrace, None, None, P::None);
         rule(RightBrace, None, None, P::None);
+        rule(LeftBracket, None, Some(Parser::slice), P::Call);
+        rule(RightBracket, None, None, P::None);
+        rule(Colon, None, None, P::None);
         rule(Comma, None, None, P::None);
         rule(Dot, None, None, P::None);
+        rule(Pipe, None, None, P::None);
         rule(Minus, Some(Parser::unary), Some(Parser::binary), P::Term);
         rule(Plus, None, Some(Parser::binary), P::Term);
         rule(Semicolon, None, None, P::None);
@@ -94,29 +103,42 @@ impl<'sc> Parser<'sc> {
         rule(Star, None, Some(Parser::binary), P::Factor);
         rule(Bang, Some(Parser::unary), None, P::None);
         rule(BangEqual, None, Some(Parser::binary), P::Equality);
+        rule(DotDot, None, Some(Parser::binary), P::Range);
+        rule(DotDotDot, None, None, P::None);
+        rule(DotDotEqual, None, Some(Parser::binary), P::Range);
         rule(Equal, None, None, P::None);
         rule(EqualEqual, None, Some(Parser::binary), P::Equality);
+        rule(FatArrow, None, None, P::None);
         rule(Greater, None, Some(Parser::binary), P::Comparison);
         rule(GreaterEqual, Some(Parser::binary), None, P::Comparison);
         rule(Less, None, Some(Parser::binary), P::Comparison);
         rule(LessEqual, None, Some(Parser::binary), P::Comparison);
+        rule(QuestionQuestion, None, Some(Parser::coalesce), P::Coalesce);
         rule(Identifier, None, None, P::None);
         rule(String, None, None, P::None);
         rule(Number, Some(Parser::number), None, P::None);
         rule(And, None, None, P::None);
+        rule(Break, Some(Parser::loop_exit), None, P::None);
+        rule(Catch, None, None, P::None);
         rule(Class, None, None, P::None);
+        rule(Const, None, None, P::None);
+        rule(Continue, Some(Parser::loop_exit), None, P::None);
         rule(Else, None, None, P::None);
         rule(False, Some(Parser::literal), None, P::None);
+        rule(Finally, None, None, P::None);
         rule(For, None, None, P::None);
         rule(Fun, None, None, P::None);
         rule(If, None, None, P::None);
+        rule(Match, Some(Parser::match_expression), None, P::None);
         rule(Nil, Some(Parser::literal), None, P::None);
         rule(Or, None, None, P::None);
         rule(Print, None, None, P::None);
         rule(Return, None, None, P::None);
         rule(Super, None, None, P::None);
         rule(This, None, None, P::None);
+        rule(Throw, Some(Parser::throw), None, P::None);
         rule(True, Some(Parser::literal), None, P::None);
+        rule(Try, None, None, P::None);
         rule(Var, None, None, P::None);
         rule(While, None, None, P::None);
         rule(Error, None, None, P::None);
@@ -160,6 +182,19 @@ impl<'sc> Parser<'sc> {
         }
     }
 
+    fn check(&self, kind: TokenType) -> bool {
+        self.current.kind == kind
+    }
+
+    fn matches(&mut self, kind: TokenType) -> bool {
+        if self.check(kind) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
     fn error_at_current(&mut self, message: &str) {
         self.error_at(self.current, message);
     }
@@ -175,7 +210,7 @@ impl<'sc> Parser<'sc> {
         self.panic_mode = true;
         self.had_error = true;
 
-        eprint!("[line {}] Error", token.line);
+        eprint!("[line {}:{}] Error", token.line, token.column);
         match token.kind {
             TokenType::Eof => eprint!(" at end"),
             TokenType::Error => {}
@@ -194,18 +229,39 @@ impl<'sc> Parser<'sc> {
     }
 
     fn emit_constant(&mut self, value: Value) {
-        let index: usize = self
-            .current_chunk
-            .add_constant(value, self.previous.line as u16);
+        let index: usize = self.current_chunk.add_constant(value);
         self.emit(Op::Constant(index));
     }
 
+    fn emit_jump(&mut self, operator: Op) -> usize {
+        self.emit(operator);
+        self.current_chunk.code.len() - 1
+    }
+
+    // Points the jump at `index` to the next operator to be emitted.
+    fn patch_jump(&mut self, index: usize) {
+        let jump = self.current_chunk.code.len() - index - 1;
+        self.current_chunk.code[index] = match self.current_chunk.code[index] {
+            Op::Jump(_) => Op::Jump(jump),
+            Op::JumpIfFalse(_) => Op::JumpIfFalse(jump),
+            Op::JumpIfNotNil(_) => Op::JumpIfNotNil(jump),
+            _ => panic!("Impossible jump type"),
+        };
+    }
+
     pub fn end_compiler(&mut self) {
         self.emit_return();
     }
 
+    // Integer literals too large for an f64 to hold exactly become big
+    // integers.
     fn number(&mut self) {
-        let value = self.previous.lexeme.parse::<f64>().unwrap();
+        let lexeme: &str = self.previous.lexeme;
+        if !lexeme.contains('.') {
+            self.emit_constant(Value::integer(BigInt::parse(lexeme)));
+            return;
+        }
+        let value = lexeme.parse::<f64>().unwrap();
         self.emit_constant(Value::Number(value));
     }
 
@@ -218,9 +274,142 @@ impl<'sc> Parser<'sc> {
         }
     }
 
+    // A parenthesized expression, or a tuple when it contains a comma or a
+    // spread. `()` is the empty tuple and `(a,)` a tuple of one.
     fn grouping(&mut self) {
+        if self.matches(TokenType::RightParen) {
+            self.emit(Op::Tuple(0));
+            return;
+        }
+
+        if self.check(TokenType::DotDotDot) {
+            self.tuple(0);
+            return;
+        }
+
         self.expression();
-        self.consume(TokenType::RightParen, "Expect ')' after expression.");
+        if !self.matches(TokenType::Comma) {
+            self.consume(TokenType::RightParen, "Expect ')' after expression.");
+            return;
+        }
+        self.tuple(1);
+    }
+
+    // Compiles the rest of a tuple literal, `pending` elements of which are
+    // already on the stack. Without spreads that is a single `Op::Tuple`.
+    // Otherwise each run of plain elements is packed into a tuple of its own,
+    // and `Op::TupleConcat` joins those with the spread tuples at runtime.
+    fn tuple(&mut self, mut pending: usize) {
+        let mut parts: usize = 0;
+        let mut spread = false;
+        while !self.check(TokenType::RightParen) && !self.check(TokenType::Eof) {
+            if self.matches(TokenType::DotDotDot) {
+                if pending > 0 {
+                    self.emit(Op::Tuple(pending));
+                    parts += 1;
+                    pending = 0;
+                }
+                self.expression();
+                parts += 1;
+                spread = true;
+            } else {
+                self.expression();
+                pending += 1;
+            }
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after tuple elements.");
+
+        if !spread {
+            self.emit(Op::Tuple(pending));
+            return;
+        }
+        if pending > 0 {
+            self.emit(Op::Tuple(pending));
+            parts += 1;
+        }
+        self.emit(Op::TupleConcat(parts));
+    }
+
+    // Compiles `match value { pattern => expression, ... }` into a chain of
+    // equality tests. The matched value stays on the stack until an arm is
+    // taken, and `Op::NoMatch` reports it when no arm applies.
+    fn match_expression(&mut self) {
+        self.expression();
+        self.consume(TokenType::LeftBrace, "Expect '{' after match value.");
+
+        let mut end_jumps: Vec<usize> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.check(TokenType::Eof) {
+            let mut body_jumps: Vec<usize> = Vec::new();
+            let mut next_arm: Option<usize> = None;
+
+            if self.check(TokenType::Identifier) && self.current.lexeme == "_" {
+                self.advance();
+            } else {
+                loop {
+                    self.emit(Op::Dup);
+                    self.pattern();
+                    self.emit(Op::Equal);
+                    let next_pattern = self.emit_jump(Op::JumpIfFalse(0));
+                    self.emit(Op::Pop);
+                    body_jumps.push(self.emit_jump(Op::Jump(0)));
+                    self.patch_jump(next_pattern);
+                    self.emit(Op::Pop);
+                    if !self.matches(TokenType::Pipe) {
+                        break;
+                    }
+                }
+                next_arm = Some(self.emit_jump(Op::Jump(0)));
+            }
+
+            for jump in body_jumps {
+                self.patch_jump(jump);
+            }
+            self.consume(TokenType::FatArrow, "Expect '=>' after pattern.");
+            self.emit(Op::Pop);
+            self.expression();
+            end_jumps.push(self.emit_jump(Op::Jump(0)));
+
+            if let Some(jump) = next_arm {
+                self.patch_jump(jump);
+            }
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after match arms.");
+
+        self.emit(Op::NoMatch);
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+    }
+
+    fn pattern(&mut self) {
+        self.advance();
+        let pattern_type: TokenType = self.previous.kind;
+        match pattern_type {
+            TokenType::Number => self.number(),
+            TokenType::False | TokenType::True | TokenType::Nil => self.literal(),
+            TokenType::Minus if self.matches(TokenType::Number) => {
+                self.number();
+                self.emit(Op::Negate);
+            }
+            _ => self.error("Expect pattern."),
+        }
+    }
+
+    // There are no loops yet, so every `break` or `continue` is outside one.
+    fn loop_exit(&mut self) {
+        let message = format!("Can't use '{}' outside of a loop.", self.previous.lexeme);
+        self.error(&message);
+    }
+
+    fn throw(&mut self) {
+        self.expression();
+        self.emit(Op::Throw);
     }
 
     fn unary(&mut self) {
@@ -250,10 +439,43 @@ impl<'sc> Parser<'sc> {
             TokenType::GreaterEqual => self.emit(Op::GreaterEqual),
             TokenType::Less => self.emit(Op::Less),
             TokenType::LessEqual => self.emit(Op::LessEqual),
+            TokenType::DotDot => self.emit(Op::Range),
+            TokenType::DotDotEqual => self.emit(Op::RangeInclusive),
             _ => {}
         }
     }
 
+    // Compiles `seq[start:end:step]`. Any of the three may be left out, which
+    // leaves nil in its place for the VM to fill in.
+    fn slice(&mut self) {
+        self.slice_bound(TokenType::Colon);
+        self.consume(TokenType::Colon, "Expect ':' in slice.");
+        self.slice_bound(TokenType::Colon);
+        if self.matches(TokenType::Colon) {
+            self.slice_bound(TokenType::RightBracket);
+        } else {
+            self.emit(Op::Nil);
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after slice.");
+        self.emit(Op::Slice);
+    }
+
+    fn slice_bound(&mut self, delimiter: TokenType) {
+        if self.check(delimiter) || self.check(TokenType::RightBracket) {
+            self.emit(Op::Nil);
+        } else {
+            self.expression();
+        }
+    }
+
+    // The right operand only runs when the left one is nil. Right associative.
+    fn coalesce(&mut self) {
+        let end_jump = self.emit_jump(Op::JumpIfNotNil(0));
+        self.emit(Op::Pop);
+        self.parse_precedence(Precedence::Coalesce);
+        self.patch_jump(end_jump);
+    }
+
     fn is_lower_precedence(&mut self, precedence: Precedence) -> bool {
         let current_precedence = self.get_rule(self.current.kind).precedence;
         precedence <= current_precedence
@@ -280,11 +502,11 @@ impl<'sc> Parser<'sc> {
     }
 
     fn get_rule(&self, kind: TokenType) -> &ParseRule<'sc> {
-        self.rules.get(&kind).clone().unwrap()
+        self.rules.get(&kind).unwrap()
     }
 }
 
-pub fn compile<'sc>(chunk: &'sc mut Chunk, source: &str) -> bool {
+pub fn compile(chunk: &mut Chunk, source: &str) -> bool {
     let mut parser: Parser = Parser::new(chunk, source);
     parser.advance();
     parser.expression();
@@ -296,5 +518,5 @@ pub fn compile<'sc>(chunk: &'sc mut Chunk, source: &str) -> bool {
             crate::debug::disasemble_chunk(parser.current_chunk, "code")
         }
     }
-    true
+    !parser.had_error
 }
@@ -21,33 +21,55 @@ pub fn disassemble_instr(chunk: &Chunk, offset: usize) -> usize {
 
     let instruction: Op = chunk.code[offset];
     match instruction {
-        Op::Constant(index) => return constant_instr("OP_CONSTANT", chunk, index, offset),
-        Op::False => return simple_instr("OP_FALSE", offset),
-        Op::True => return simple_instr("OP_TRUE", offset),
-        Op::Nil => return simple_instr("OP_NIL", offset),
-        Op::Negate => return simple_instr("OP_NEGATE", offset),
-        Op::Add => return simple_instr("OP_ADD", offset),
-        Op::Subtract => return simple_instr("OP_SUBTRACT", offset),
-        Op::Multiply => return simple_instr("OP_MULTIPLY", offset),
-        Op::Divide => return simple_instr("OP_DIVIDE", offset),
-        Op::Not => return simple_instr("OP_NOT", offset),
-        Op::Return => return simple_instr("OP_RETURN", offset),
-        Op::Equal => return simple_instr("OP_EQUAL", offset),
-        Op::NotEqual => return simple_instr("OP_NOT_EQUAL", offset),
-        Op::Greater => return simple_instr("OP_GREATER", offset),
-        Op::Less => return simple_instr("OP_GREATER_EQUAL", offset),
-        Op::LessEqual => return simple_instr("OP_LESS", offset),
-        Op::GreaterEqual => return simple_instr("OP_LESS_EQUAL", offset),
+        Op::Constant(index) => constant_instr("OP_CONSTANT", chunk, index, offset),
+        Op::False => simple_instr("OP_FALSE", offset),
+        Op::True => simple_instr("OP_TRUE", offset),
+        Op::Nil => simple_instr("OP_NIL", offset),
+        Op::Negate => simple_instr("OP_NEGATE", offset),
+        Op::Add => simple_instr("OP_ADD", offset),
+        Op::Subtract => simple_instr("OP_SUBTRACT", offset),
+        Op::Multiply => simple_instr("OP_MULTIPLY", offset),
+        Op::Divide => simple_instr("OP_DIVIDE", offset),
+        Op::Not => simple_instr("OP_NOT", offset),
+        Op::Return => simple_instr("OP_RETURN", offset),
+        Op::Equal => simple_instr("OP_EQUAL", offset),
+        Op::NotEqual => simple_instr("OP_NOT_EQUAL", offset),
+        Op::Greater => simple_instr("OP_GREATER", offset),
+        Op::Less => simple_instr("OP_GREATER_EQUAL", offset),
+        Op::LessEqual => simple_instr("OP_LESS", offset),
+        Op::GreaterEqual => simple_instr("OP_LESS_EQUAL", offset),
+        Op::Pop => simple_instr("OP_POP", offset),
+        Op::Dup => simple_instr("OP_DUP", offset),
+        Op::Jump(jump) => jump_instr("OP_JUMP", jump, offset),
+        Op::JumpIfFalse(jump) => jump_instr("OP_JUMP_IF_FALSE", jump, offset),
+        Op::JumpIfNotNil(jump) => jump_instr("OP_JUMP_IF_NOT_NIL", jump, offset),
+        Op::NoMatch => simple_instr("OP_NO_MATCH", offset),
+        Op::Throw => simple_instr("OP_THROW", offset),
+        Op::Tuple(count) => count_instr("OP_TUPLE", count, offset),
+        Op::TupleConcat(count) => count_instr("OP_TUPLE_CONCAT", count, offset),
+        Op::Range => simple_instr("OP_RANGE", offset),
+        Op::RangeInclusive => simple_instr("OP_RANGE_INCLUSIVE", offset),
+        Op::Slice => simple_instr("OP_SLICE", offset),
     }
 }
 
 fn simple_instr(name: &str, offset: usize) -> usize {
     println!("{}", name);
-    return offset + 1;
+    offset + 1
+}
+
+fn count_instr(name: &str, count: usize, offset: usize) -> usize {
+    println!("{:16} {:4}", name, count);
+    offset + 1
+}
+
+fn jump_instr(name: &str, jump: usize, offset: usize) -> usize {
+    println!("{:16} {:4} -> {}", name, offset, offset + 1 + jump);
+    offset + 1
 }
 
 fn constant_instr(name: &str, chunk: &Chunk, index: usize, offset: usize) -> usize {
-    let constant: Value = chunk.values[index];
+    let constant: &Value = &chunk.values[index];
     println!("{} {:4} '{:?}'", name, index, constant);
-    return offset + 1;
+    offset + 1
 }
@@ -1,14 +1,18 @@
+mod bigint;
+mod checker;
 mod chunk;
 mod compiler;
 mod debug;
 mod scanner;
 mod value;
 mod vm;
+mod xid;
 
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 
+use chunk::Chunk;
 use vm::{LoxError, VM};
 
 fn repl(vm: &mut VM) {
@@ -26,14 +30,18 @@ fn repl(vm: &mut VM) {
     }
 }
 
-fn run_file(vm: &mut VM, path: &str) {
-    let code = match fs::read_to_string(path) {
+fn read_file(path: &str) -> String {
+    match fs::read_to_string(path) {
         Ok(content) => content,
         Err(error) => {
             eprintln!("Error reading file {}: {}", path, error);
             std::process::exit(74);
         }
-    };
+    }
+}
+
+fn run_file(vm: &mut VM, path: &str) {
+    let code = read_file(path);
     if let Err(error) = vm.interpret(&code) {
         match error {
             LoxError::CompileError => std::process::exit(65),
@@ -42,6 +50,14 @@ fn run_file(vm: &mut VM, path: &str) {
     }
 }
 
+fn check_file(path: &str) {
+    let code = read_file(path);
+    let mut chunk: Chunk = Chunk::new();
+    if !compiler::compile(&mut chunk, &code) || !checker::check(&chunk) {
+        std::process::exit(65);
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -50,8 +66,9 @@ fn main() {
     match args.len() {
         1 => repl(&mut vm),
         2 => run_file(&mut vm, &args[1]),
+        3 if args[1] == "check" => check_file(&args[2]),
         _ => {
-            eprintln!("Usage: lox-rs [path]");
+            eprintln!("Usage: lox-rs [check] [path]");
             std::process::exit(64);
         }
     }
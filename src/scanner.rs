@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
+use crate::xid;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum TokenType {
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
+    Pipe,
     Minus,
     Plus,
     Semicolon,
@@ -16,31 +22,44 @@ pub enum TokenType {
 
     Bang,
     BangEqual,
+    DotDot,
+    DotDotDot,
+    DotDotEqual,
     Equal,
     EqualEqual,
+    FatArrow,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
+    QuestionQuestion,
 
     Identifier,
     String,
     Number,
 
     And,
+    Break,
+    Catch,
     Class,
+    Const,
+    Continue,
     Else,
     False,
+    Finally,
     For,
     Fun,
     If,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -52,6 +71,7 @@ pub enum TokenType {
 pub struct Token<'sc> {
     pub kind: TokenType,
     pub line: usize,
+    pub column: usize,
     pub lexeme: &'sc str,
 }
 
@@ -60,6 +80,7 @@ impl<'sc> Token<'sc> {
         Self {
             kind: TokenType::Nil,
             line: 0,
+            column: 0,
             lexeme: "nil",
         }
     }
@@ -70,26 +91,37 @@ pub struct Scanner<'sc> {
     code: &'sc str,
     start: usize,
     current: usize,
+    start_line: usize,
+    start_column: usize,
+    column: usize,
     pub line: usize,
 }
 
 impl<'sc> Scanner<'sc> {
     pub fn new(code: &'sc str) -> Self {
-        let mut keywords = HashMap::with_capacity(16);
+        let mut keywords = HashMap::with_capacity(24);
         keywords.insert("and", TokenType::And);
+        keywords.insert("break", TokenType::Break);
+        keywords.insert("catch", TokenType::Catch);
         keywords.insert("class", TokenType::Class);
+        keywords.insert("const", TokenType::Const);
+        keywords.insert("continue", TokenType::Continue);
         keywords.insert("else", TokenType::Else);
         keywords.insert("false", TokenType::False);
+        keywords.insert("finally", TokenType::Finally);
         keywords.insert("for", TokenType::For);
         keywords.insert("fun", TokenType::Fun);
         keywords.insert("if", TokenType::If);
+        keywords.insert("match", TokenType::Match);
         keywords.insert("nil", TokenType::Nil);
         keywords.insert("or", TokenType::Or);
         keywords.insert("print", TokenType::Print);
         keywords.insert("return", TokenType::Return);
         keywords.insert("super", TokenType::Super);
         keywords.insert("this", TokenType::This);
+        keywords.insert("throw", TokenType::Throw);
         keywords.insert("true", TokenType::True);
+        keywords.insert("try", TokenType::Try);
         keywords.insert("var", TokenType::Var);
         keywords.insert("while", TokenType::While);
 
@@ -98,41 +130,61 @@ impl<'sc> Scanner<'sc> {
             code,
             start: 0,
             current: 0,
-            line: 0,
+            start_line: 1,
+            start_column: 0,
+            column: 0,
+            line: 1,
         }
     }
 
     pub fn scan_token(&mut self) -> Token<'sc> {
         self.skip_whitespace();
         self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column;
 
         if self.is_at_end() {
             return self.make_token(TokenType::Eof);
         }
 
         match self.advance() {
-            b'(' => self.make_token(TokenType::LeftParen),
-            b')' => self.make_token(TokenType::RightParen),
-            b'{' => self.make_token(TokenType::LeftBrace),
-            b'}' => self.make_token(TokenType::RightBrace),
-            b';' => self.make_token(TokenType::Semicolon),
-            b',' => self.make_token(TokenType::Comma),
-            b'.' => self.make_token(TokenType::Dot),
-            b'-' => self.make_token(TokenType::Minus),
-            b'+' => self.make_token(TokenType::Plus),
-            b'/' => self.make_token(TokenType::Slash),
-            b'*' => self.make_token(TokenType::Star),
-
-            b'!' if self.matches(b'=') => self.make_token(TokenType::BangEqual),
-            b'!' => self.make_token(TokenType::Bang),
-            b'=' if self.matches(b'=') => self.make_token(TokenType::EqualEqual),
-            b'=' => self.make_token(TokenType::Equal),
-            b'<' if self.matches(b'=') => self.make_token(TokenType::LessEqual),
-            b'<' => self.make_token(TokenType::Less),
-            b'>' if self.matches(b'=') => self.make_token(TokenType::GreaterEqual),
-            b'>' => self.make_token(TokenType::Greater),
-
-            b'"' => self.string(),
+            '(' => self.make_token(TokenType::LeftParen),
+            ')' => self.make_token(TokenType::RightParen),
+            '{' => self.make_token(TokenType::LeftBrace),
+            '}' => self.make_token(TokenType::RightBrace),
+            '[' => self.make_token(TokenType::LeftBracket),
+            ']' => self.make_token(TokenType::RightBracket),
+            ';' => self.make_token(TokenType::Semicolon),
+            ':' => self.make_token(TokenType::Colon),
+            ',' => self.make_token(TokenType::Comma),
+            '.' if self.matches('.') => {
+                if self.matches('=') {
+                    self.make_token(TokenType::DotDotEqual)
+                } else if self.matches('.') {
+                    self.make_token(TokenType::DotDotDot)
+                } else {
+                    self.make_token(TokenType::DotDot)
+                }
+            }
+            '.' => self.make_token(TokenType::Dot),
+            '|' => self.make_token(TokenType::Pipe),
+            '-' => self.make_token(TokenType::Minus),
+            '+' => self.make_token(TokenType::Plus),
+            '/' => self.make_token(TokenType::Slash),
+            '*' => self.make_token(TokenType::Star),
+
+            '!' if self.matches('=') => self.make_token(TokenType::BangEqual),
+            '!' => self.make_token(TokenType::Bang),
+            '=' if self.matches('=') => self.make_token(TokenType::EqualEqual),
+            '=' if self.matches('>') => self.make_token(TokenType::FatArrow),
+            '=' => self.make_token(TokenType::Equal),
+            '<' if self.matches('=') => self.make_token(TokenType::LessEqual),
+            '<' => self.make_token(TokenType::Less),
+            '>' if self.matches('=') => self.make_token(TokenType::GreaterEqual),
+            '>' => self.make_token(TokenType::Greater),
+            '?' if self.matches('?') => self.make_token(TokenType::QuestionQuestion),
+
+            '"' => self.string(),
             c if is_digit(c) => self.number(),
             c if is_alpha(c) => self.identifier(),
 
@@ -140,29 +192,35 @@ impl<'sc> Scanner<'sc> {
         }
     }
 
-    fn peek(&self) -> u8 {
-        self.code.as_bytes()[self.current]
+    fn peek(&self) -> char {
+        self.code[self.current..].chars().next().unwrap_or('\0')
     }
 
-    fn peek_next(&self) -> u8 {
-        if self.current == self.code.len() {
-            b'\0'
-        } else {
-            self.code.as_bytes()[self.current + 1]
-        }
+    fn peek_next(&self) -> char {
+        let mut chars = self.code[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
     }
 
-    fn advance(&mut self) -> u8 {
-        let c: u8 = self.peek();
-        self.current += 1;
+    // Steps over one whole character, keeping `line` and `column` in step with
+    // it. Columns count characters, not bytes.
+    fn advance(&mut self) -> char {
+        let c: char = self.peek();
+        self.current += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
         c
     }
 
-    fn matches(&mut self, expected: u8) -> bool {
+    fn matches(&mut self, expected: char) -> bool {
         if self.is_at_end() || self.peek() != expected {
             false
         } else {
-            self.current += 1;
+            self.advance();
             true
         }
     }
@@ -174,14 +232,10 @@ impl<'sc> Scanner<'sc> {
     fn skip_whitespace(&mut self) {
         while !self.is_at_end() {
             let char = self.peek();
-            let _: u8 = match char {
-                b' ' | b'\r' | b'\t' => self.advance(),
-                b'\n' => {
-                    self.line += 1;
-                    self.advance()
-                }
-                b'/' if self.peek_next() == b'/' => {
-                    while self.peek() != b'\n' && !self.is_at_end() {
+            let _: char = match char {
+                ' ' | '\r' | '\t' | '\n' => self.advance(),
+                '/' if self.peek_next() == '/' => {
+                    while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
                     return;
@@ -199,10 +253,7 @@ impl<'sc> Scanner<'sc> {
     }
 
     fn string(&mut self) -> Token<'sc> {
-        while self.peek() != b'"' && !self.is_at_end() {
-            if self.peek() == b'\n' {
-                self.line += 1;
-            }
+        while self.peek() != '"' && !self.is_at_end() {
             self.advance();
         }
 
@@ -219,18 +270,18 @@ impl<'sc> Scanner<'sc> {
             self.advance();
         }
 
-        if self.peek() == b'.' && is_digit(self.peek_next()) {
+        if self.peek() == '.' && is_digit(self.peek_next()) {
             self.advance();
             while is_digit(self.peek()) {
                 self.advance();
             }
         }
 
-        return self.make_token(TokenType::Number);
+        self.make_token(TokenType::Number)
     }
 
     fn identifier(&mut self) -> Token<'sc> {
-        while is_alpha(self.peek()) || is_digit(self.peek()) {
+        while is_alphanumeric(self.peek()) {
             self.advance();
         }
 
@@ -239,8 +290,9 @@ impl<'sc> Scanner<'sc> {
 
     fn make_token(&self, kind: TokenType) -> Token<'sc> {
         Token {
-            kind: kind,
-            line: self.line,
+            kind,
+            line: self.start_line,
+            column: self.start_column + 1,
             lexeme: &self.code[self.start..self.current],
         }
     }
@@ -248,16 +300,22 @@ impl<'sc> Scanner<'sc> {
     fn error_token(&self, message: &'sc str) -> Token<'sc> {
         Token {
             kind: TokenType::Error,
-            line: self.line,
-            lexeme: &message,
+            line: self.start_line,
+            column: self.start_column + 1,
+            lexeme: message,
         }
     }
 }
 
-fn is_digit(c: u8) -> bool {
+fn is_digit(c: char) -> bool {
     c.is_ascii_digit()
 }
 
-fn is_alpha(c: u8) -> bool {
-    c.is_ascii_alphabetic() || c == b'_'
+// Identifiers follow Unicode's XID_Start and XID_Continue properties.
+fn is_alpha(c: char) -> bool {
+    xid::is_xid_start(c) || c == '_'
+}
+
+fn is_alphanumeric(c: char) -> bool {
+    xid::is_xid_continue(c)
 }
@@ -1,10 +1,16 @@
 use std::fmt;
+use std::rc::Rc;
 
-#[derive(Clone, Copy, PartialEq)]
+use crate::bigint::BigInt;
+
+#[derive(Clone)]
 pub enum Value {
     Number(f64),
+    BigInt(Rc<BigInt>),
     Nil,
     Boolean(bool),
+    Tuple(Rc<[Value]>),
+    Range(Rc<(Value, Value)>, bool),
 }
 
 impl Value {
@@ -15,14 +21,75 @@ impl Value {
             _ => false,
         }
     }
+
+    // Integers beyond what an f64 holds exactly stay big, the rest become
+    // plain numbers again.
+    pub fn integer(value: BigInt) -> Self {
+        if value.fits_f64() {
+            Value::Number(value.to_f64())
+        } else {
+            Value::BigInt(Rc::new(value))
+        }
+    }
+
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            Value::Number(value) => BigInt::from_f64(*value),
+            Value::BigInt(value) => Some(BigInt::clone(value)),
+            _ => None,
+        }
+    }
+
+    pub fn to_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            Value::BigInt(value) => Some(value.to_f64()),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => {
+                match (self.to_bigint(), other.to_bigint()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                }
+            }
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Range(a, b), Value::Range(x, y)) => a == x && b == y,
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Number(value) => write!(f, "{}", value),
+            Value::BigInt(value) => write!(f, "{:?}", value),
             Value::Nil => write!(f, "nil"),
             Value::Boolean(value) => write!(f, "{}", value),
+            Value::Tuple(values) => {
+                write!(f, "(")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", value)?;
+                }
+                if values.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Value::Range(bounds, false) => write!(f, "{:?}..{:?}", bounds.0, bounds.1),
+            Value::Range(bounds, true) => write!(f, "{:?}..={:?}", bounds.0, bounds.1),
         }
     }
 }
@@ -1,3 +1,6 @@
+use std::rc::Rc;
+
+use crate::bigint::{BigInt, MAX_EXACT};
 use crate::chunk::*;
 use crate::compiler::*;
 use crate::value::*;
@@ -18,6 +21,76 @@ pub enum BinOp {
     LessEqual,
 }
 
+// Reads an optional slice bound. Returns None when the bound is not an
+// integer. Bounds beyond i64 saturate, which the caller clamps like any other
+// out of range bound.
+fn slice_index(value: &Value) -> Option<Option<i64>> {
+    match value {
+        Value::Nil => Some(None),
+        Value::Number(index) if index.fract() == 0.0 => Some(Some(*index as i64)),
+        Value::BigInt(index) => Some(Some(index.to_f64() as i64)),
+        _ => None,
+    }
+}
+
+// Slices the same way Python does: negative indices count from the end,
+// out of range bounds are clamped and a negative step walks backwards.
+fn slice(
+    values: &[Value],
+    start: &Value,
+    end: &Value,
+    step: &Value,
+) -> Result<Vec<Value>, &'static str> {
+    let length = values.len() as i64;
+    let step = match slice_index(step) {
+        Some(None) => 1,
+        Some(Some(0)) => return Err("Slice step cannot be zero."),
+        // Any step longer than the tuple takes at most one element, so
+        // clamping it keeps the walk below from overflowing.
+        Some(Some(step)) => step.clamp(-length.max(1), length.max(1)),
+        None => return Err("Slice indices must be integers."),
+    };
+    let (lower, upper) = if step > 0 {
+        (0, length)
+    } else {
+        (-1, length - 1)
+    };
+    let clamp = |index: i64| {
+        if index < 0 {
+            (index + length).max(lower)
+        } else {
+            index.min(upper)
+        }
+    };
+    let (start, end) = match (slice_index(start), slice_index(end)) {
+        (Some(start), Some(end)) => (
+            start.map_or(if step > 0 { lower } else { upper }, clamp),
+            end.map_or(if step > 0 { upper } else { lower }, clamp),
+        ),
+        _ => return Err("Slice indices must be integers."),
+    };
+
+    let mut slice: Vec<Value> = Vec::new();
+    let mut index = start;
+    while (step > 0 && index < end) || (step < 0 && index > end) {
+        slice.push(values[index as usize].clone());
+        index += step;
+    }
+    Ok(slice)
+}
+
+impl BinOp {
+    fn loses_precision(&self, a: f64, b: f64) -> bool {
+        let result = match self {
+            BinOp::Add => a + b,
+            BinOp::Subtract => a - b,
+            BinOp::Multiply => a * b,
+            _ => return false,
+        };
+        a.fract() == 0.0 && b.fract() == 0.0 && result.abs() >= MAX_EXACT
+    }
+}
+
 pub struct VM {
     pub chunk: Chunk,
     pub stack: Vec<Value>,
@@ -44,7 +117,7 @@ impl VM {
 
     fn runtime_error(&self, message: &str) -> Result<(), LoxError> {
         eprintln!("{}", message);
-        let line: u16 = self.chunk.get_line(self.ip);
+        let line: u16 = self.chunk.get_line(self.ip - 1);
         eprintln!("[line {}] in script", line);
         Err(LoxError::RuntimeError)
     }
@@ -58,29 +131,101 @@ impl VM {
     }
 
     fn peek(&mut self, distance: usize) -> Value {
-        return self.stack[self.stack.len() - 1 - distance];
+        self.stack[self.stack.len() - 1 - distance].clone()
     }
 
+    // Integer arithmetic moves to big integers once a result is too large for
+    // an f64 to hold exactly. Mixing a big integer with an integral number stays
+    // exact, anything else falls back to floating point.
     fn binary_op(&mut self, binop: BinOp) -> Result<(), LoxError> {
-        let operands = (self.pop(), self.pop());
-        match operands {
-            (Value::Number(b), Value::Number(a)) => {
-                match binop {
-                    BinOp::Add => self.push(Value::Number(a + b)),
-                    BinOp::Subtract => self.push(Value::Number(a - b)),
-                    BinOp::Multiply => self.push(Value::Number(a * b)),
-                    BinOp::Divide => self.push(Value::Number(a / b)),
-                    BinOp::Greater => self.push(Value::Boolean(a > b)),
-                    BinOp::GreaterEqual => self.push(Value::Boolean(a >= b)),
-                    BinOp::Less => self.push(Value::Boolean(a < b)),
-                    BinOp::LessEqual => self.push(Value::Boolean(a <= b)),
-                }
+        let b: Value = self.pop();
+        let a: Value = self.pop();
+        if let (Value::Number(a), Value::Number(b)) = (&a, &b) {
+            if !binop.loses_precision(*a, *b) {
+                self.number_op(binop, *a, *b);
+                return Ok(());
+            }
+        }
+        if let (Some(a), Some(b)) = (a.to_bigint(), b.to_bigint()) {
+            self.bigint_op(binop, a, b);
+            return Ok(());
+        }
+        match (a.to_number(), b.to_number()) {
+            (Some(a), Some(b)) => {
+                self.number_op(binop, a, b);
                 Ok(())
             }
             _ => self.runtime_error("Operands must be numbers."),
         }
     }
 
+    fn number_op(&mut self, binop: BinOp, a: f64, b: f64) {
+        match binop {
+            BinOp::Add => self.push(Value::Number(a + b)),
+            BinOp::Subtract => self.push(Value::Number(a - b)),
+            BinOp::Multiply => self.push(Value::Number(a * b)),
+            BinOp::Divide => self.push(Value::Number(a / b)),
+            BinOp::Greater => self.push(Value::Boolean(a > b)),
+            BinOp::GreaterEqual => self.push(Value::Boolean(a >= b)),
+            BinOp::Less => self.push(Value::Boolean(a < b)),
+            BinOp::LessEqual => self.push(Value::Boolean(a <= b)),
+        }
+    }
+
+    fn bigint_op(&mut self, binop: BinOp, a: BigInt, b: BigInt) {
+        match binop {
+            BinOp::Add => self.push(Value::integer(a.add(&b))),
+            BinOp::Subtract => self.push(Value::integer(a.subtract(&b))),
+            BinOp::Multiply => self.push(Value::integer(a.multiply(&b))),
+            // Inexact quotients become numbers, unless they are beyond what an
+            // f64 can hold at all, in which case they keep their integer part.
+            BinOp::Divide => match a.divide(&b) {
+                Some((quotient, remainder)) if remainder.is_zero() => {
+                    self.push(Value::integer(quotient))
+                }
+                Some((quotient, _)) if quotient.to_f64().is_infinite() => {
+                    self.push(Value::integer(quotient))
+                }
+                Some((quotient, remainder)) => {
+                    let fraction: f64 = remainder.ratio(&b);
+                    self.push(Value::Number(quotient.to_f64() + fraction))
+                }
+                None => self.push(Value::Number(a.to_f64() / b.to_f64())),
+            },
+            BinOp::Greater => self.push(Value::Boolean(a > b)),
+            BinOp::GreaterEqual => self.push(Value::Boolean(a >= b)),
+            BinOp::Less => self.push(Value::Boolean(a < b)),
+            BinOp::LessEqual => self.push(Value::Boolean(a <= b)),
+        }
+    }
+
+    fn range(&mut self, inclusive: bool) -> Result<(), LoxError> {
+        let end: Value = self.pop();
+        let start: Value = self.pop();
+        if start.to_number().is_none() || end.to_number().is_none() {
+            return self.runtime_error("Range bounds must be numbers.");
+        }
+        self.push(Value::Range(Rc::new((start, end)), inclusive));
+        Ok(())
+    }
+
+    fn slice(&mut self) -> Result<(), LoxError> {
+        let step: Value = self.pop();
+        let end: Value = self.pop();
+        let start: Value = self.pop();
+        let values = match self.pop() {
+            Value::Tuple(values) => values,
+            _ => return self.runtime_error("Only tuples can be sliced."),
+        };
+        match slice(&values, &start, &end, &step) {
+            Ok(slice) => {
+                self.push(Value::Tuple(slice.into()));
+                Ok(())
+            }
+            Err(message) => self.runtime_error(message),
+        }
+    }
+
     fn run(&mut self) -> Result<(), LoxError> {
         loop {
             let instruction: Op = self.chunk.code[self.ip];
@@ -94,7 +239,7 @@ impl VM {
 
             match instruction {
                 Op::Constant(index) => {
-                    let value: Value = self.chunk.values[index];
+                    let value: Value = self.chunk.values[index].clone();
                     self.push(value);
                 }
                 Op::False => self.push(Value::Boolean(false)),
@@ -105,6 +250,10 @@ impl VM {
                         self.pop();
                         self.push(Value::Number(-value));
                     }
+                    Value::BigInt(value) => {
+                        self.pop();
+                        self.push(Value::BigInt(Rc::new(value.negate())));
+                    }
                     _ => return self.runtime_error("Operand must be a number."),
                 },
                 Op::Add => self.binary_op(BinOp::Add)?,
@@ -129,6 +278,49 @@ impl VM {
                 Op::GreaterEqual => self.binary_op(BinOp::GreaterEqual)?,
                 Op::Less => self.binary_op(BinOp::Less)?,
                 Op::LessEqual => self.binary_op(BinOp::LessEqual)?,
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Dup => {
+                    let value: Value = self.peek(0);
+                    self.push(value);
+                }
+                Op::Jump(offset) => self.ip += offset,
+                Op::JumpIfFalse(offset) => {
+                    if self.peek(0).is_falsey() {
+                        self.ip += offset;
+                    }
+                }
+                Op::JumpIfNotNil(offset) => {
+                    if !matches!(self.peek(0), Value::Nil) {
+                        self.ip += offset;
+                    }
+                }
+                Op::NoMatch => {
+                    let message = format!("No match arm for value {:?}.", self.peek(0));
+                    return self.runtime_error(&message);
+                }
+                Op::Throw => {
+                    let message = format!("Uncaught {:?}.", self.pop());
+                    return self.runtime_error(&message);
+                }
+                Op::Tuple(count) => {
+                    let values: Rc<[Value]> = self.stack.split_off(self.stack.len() - count).into();
+                    self.push(Value::Tuple(values));
+                }
+                Op::TupleConcat(count) => {
+                    let mut values: Vec<Value> = Vec::new();
+                    for part in self.stack.split_off(self.stack.len() - count) {
+                        match part {
+                            Value::Tuple(part) => values.extend(part.iter().cloned()),
+                            _ => return self.runtime_error("Can only spread tuples."),
+                        }
+                    }
+                    self.push(Value::Tuple(values.into()));
+                }
+                Op::Range => self.range(false)?,
+                Op::RangeInclusive => self.range(true)?,
+                Op::Slice => self.slice()?,
                 Op::Return => {
                     println!("{:?}", self.pop());
                     return Ok(());
@@ -137,3 +329,127 @@ impl VM {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[f64]) -> Vec<Value> {
+        values.iter().map(|&value| Value::Number(value)).collect()
+    }
+
+    fn bound(index: Option<f64>) -> Value {
+        index.map_or(Value::Nil, Value::Number)
+    }
+
+    fn slice_of(
+        length: usize,
+        start: Option<f64>,
+        end: Option<f64>,
+        step: Option<f64>,
+    ) -> Result<Vec<Value>, &'static str> {
+        let values: Vec<Value> = (1..=length)
+            .map(|value| Value::Number(value as f64))
+            .collect();
+        slice(&values, &bound(start), &bound(end), &bound(step))
+    }
+
+    #[test]
+    fn slice_defaults_and_reverse() {
+        assert_eq!(
+            slice_of(5, None, None, None),
+            Ok(numbers(&[1.0, 2.0, 3.0, 4.0, 5.0]))
+        );
+        assert_eq!(
+            slice_of(5, None, None, Some(-1.0)),
+            Ok(numbers(&[5.0, 4.0, 3.0, 2.0, 1.0]))
+        );
+        assert_eq!(
+            slice_of(5, None, None, Some(2.0)),
+            Ok(numbers(&[1.0, 3.0, 5.0]))
+        );
+        assert_eq!(
+            slice_of(5, Some(4.0), Some(0.0), Some(-2.0)),
+            Ok(numbers(&[5.0, 3.0]))
+        );
+    }
+
+    #[test]
+    fn slice_negative_indices() {
+        assert_eq!(
+            slice_of(5, Some(-2.0), None, None),
+            Ok(numbers(&[4.0, 5.0]))
+        );
+        assert_eq!(
+            slice_of(5, None, Some(-3.0), None),
+            Ok(numbers(&[1.0, 2.0]))
+        );
+        assert_eq!(
+            slice_of(5, Some(-1.0), Some(-4.0), Some(-1.0)),
+            Ok(numbers(&[5.0, 4.0, 3.0]))
+        );
+    }
+
+    #[test]
+    fn slice_clamps_out_of_range_bounds() {
+        assert_eq!(
+            slice_of(3, Some(-100.0), Some(100.0), None),
+            Ok(numbers(&[1.0, 2.0, 3.0]))
+        );
+        assert_eq!(
+            slice_of(3, Some(100.0), Some(-100.0), Some(-1.0)),
+            Ok(numbers(&[3.0, 2.0, 1.0]))
+        );
+        assert_eq!(slice_of(3, Some(5.0), Some(1.0), None), Ok(Vec::new()));
+        assert_eq!(slice_of(3, Some(-100.0), Some(-50.0), None), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn slice_empty_tuple() {
+        assert_eq!(slice_of(0, None, None, Some(-1.0)), Ok(Vec::new()));
+        assert_eq!(
+            slice_of(0, Some(-1.0), Some(1.0), Some(-3.0)),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn slice_huge_step() {
+        let huge = Value::integer(BigInt::parse("99999999999999999999999"));
+        let values = numbers(&[1.0, 2.0, 3.0]);
+        assert_eq!(
+            slice(&values, &Value::Nil, &Value::Nil, &huge),
+            Ok(numbers(&[1.0]))
+        );
+        assert_eq!(
+            slice(&values, &Value::Number(1.0), &Value::Nil, &huge),
+            Ok(numbers(&[2.0]))
+        );
+        let negative = match &huge {
+            Value::BigInt(value) => Value::BigInt(Rc::new(value.negate())),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            slice(&values, &Value::Nil, &Value::Nil, &negative),
+            Ok(numbers(&[3.0]))
+        );
+        assert_eq!(slice_of(3, None, None, Some(1e22)), Ok(numbers(&[1.0])));
+    }
+
+    #[test]
+    fn slice_errors() {
+        assert_eq!(
+            slice_of(3, None, None, Some(0.0)),
+            Err("Slice step cannot be zero.")
+        );
+        assert_eq!(
+            slice_of(3, Some(0.5), None, None),
+            Err("Slice indices must be integers.")
+        );
+        let values = numbers(&[1.0]);
+        assert_eq!(
+            slice(&values, &Value::Boolean(true), &Value::Nil, &Value::Nil),
+            Err("Slice indices must be integers.")
+        );
+    }
+}